@@ -0,0 +1,161 @@
+//! Generates fresh, guaranteed-solvable `Farm` boards, for an endless supply
+//! of puzzles beyond the physical ThinkFun card set.
+//!
+//! Every move advances the UFO exactly two cells in one direction, so its
+//! row parity and column parity are each invariant for the rest of the
+//! game: a cell is only ever reachable if it shares *both* parities with
+//! the UFO's start. Only even rows/columns touch the board edge (row/column
+//! 0 and `height - 1`/`width - 1` are both even), so a cell with an odd row
+//! *and* an odd column can never exit -- only one with an even row or an even
+//! column can. Placing the UFO, cows and red bull on uniformly random
+//! interior cells and rejecting unsolvable rolls (as an earlier version of
+//! this module did) satisfies that constraint only about 1 in 10^5 tries,
+//! which exhausts `MAX_ATTEMPTS` for nearly every seed. Instead, the
+//! required fixtures are placed by construction on a single exitable
+//! parity class, and the silo/obstacles are confined to cells the solution
+//! never has to cross, so every generated board is solvable.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::{Farm, IotCS, Object, Pos};
+
+const MAX_ATTEMPTS: u32 = 10_000;
+
+/// How hard a generated board should be to solve, judged by the solver's own
+/// move count and number of expanded search states.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Expert,
+}
+impl Difficulty {
+    /// The board dimensions to roll candidates on. The six required
+    /// fixtures always sit on one parity class of the interior, so a
+    /// bigger board spreads them farther apart in 2-cell hops and is what
+    /// actually drives move count up; a fixed 7x7 board only has a 3x2
+    /// lattice of eligible cells and can never produce a solution longer
+    /// than a handful of moves no matter how the thresholds are tuned.
+    fn dims(self) -> (usize, usize) {
+        match self {
+            Difficulty::Easy => (7, 7),
+            Difficulty::Medium => (11, 11),
+            Difficulty::Expert => (17, 17),
+        }
+    }
+
+    fn accepts(self, move_count: usize, expanded_nodes: usize) -> bool {
+        match self {
+            Difficulty::Easy => move_count <= 14,
+            Difficulty::Medium => (15..=24).contains(&move_count) && expanded_nodes >= 50,
+            Difficulty::Expert => move_count >= 25 && expanded_nodes >= 200,
+        }
+    }
+}
+
+/// Generates a fresh, guaranteed-solvable board from `seed`. Equivalent to
+/// `generate_with_difficulty(seed, Difficulty::Medium)`.
+pub fn generate(seed: u64) -> Option<Farm> {
+    generate_with_difficulty(seed, Difficulty::Medium)
+}
+
+/// Generates a fresh board from `seed`, re-rolling the layout until the A*
+/// solver reports it is solvable with a move count and expanded-node count
+/// matching `difficulty`. Returns `None` if no such board is found within
+/// `MAX_ATTEMPTS` re-rolls, so callers can decide how to handle an
+/// unsatisfiable request (e.g. falling back to an easier difficulty) instead
+/// of crashing.
+pub fn generate_with_difficulty(seed: u64, difficulty: Difficulty) -> Option<Farm> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (width, height) = difficulty.dims();
+    for _ in 0..MAX_ATTEMPTS {
+        let farm = random_layout(&mut rng, width, height);
+        let (move_count, expanded_nodes) = match IotCS::new(&farm).solve_astar_with_stats() {
+            Some((moves, expanded_nodes)) => (moves.len(), expanded_nodes),
+            None => continue,
+        };
+        if difficulty.accepts(move_count, expanded_nodes) {
+            return Some(farm);
+        }
+    }
+    None
+}
+
+/// Builds one candidate board: a border wall frame; the UFO, the four
+/// colored cows and the red bull placed on six randomly chosen interior cells
+/// with an odd row and an even column (the single parity class `generate_with_difficulty`
+/// relies on being mutually reachable and exitable); and the silo plus a
+/// scattering of height-gated obstacles confined to interior cells with an
+/// even row and an odd column, which the solution never needs to cross. The
+/// remaining interior cells (odd row and odd column, or even row and even
+/// column) are left empty -- they are exactly the cells the UFO passes over
+/// mid-hop when moving between solution cells or out to the edge, so an
+/// obstacle there could block the very path this layout guarantees.
+fn random_layout(rng: &mut StdRng, width: usize, height: usize) -> Farm {
+    let mut farm = Farm::new(width, height);
+
+    for pos in Pos::values(width, height) {
+        let (x, y) = pos.xy();
+        let on_top_or_bottom = x == 0 || x == height - 1;
+        let on_left_or_right = y == 0 || y == width - 1;
+        let obj = if on_top_or_bottom && on_left_or_right {
+            Object::Corner
+        } else if on_top_or_bottom {
+            Object::Wall2
+        } else if on_left_or_right {
+            Object::Wall1
+        } else {
+            Object::Empty
+        };
+        *farm.get_mut(pos) = Some(obj);
+    }
+
+    let mut solution_cells: Vec<Pos> = Pos::values(width, height)
+        .filter(|pos| {
+            let (x, y) = pos.xy();
+            x != 0 && x != height - 1 && y != 0 && y != width - 1 && x % 2 == 1 && y % 2 == 0
+        })
+        .collect();
+    solution_cells.shuffle(rng);
+
+    let fixtures = [
+        Object::UFO,
+        Object::AzureCow,
+        Object::YellowCow,
+        Object::PurpleCow,
+        Object::OrangeCow,
+        Object::RedBull,
+    ];
+    for (pos, obj) in solution_cells.into_iter().zip(fixtures) {
+        *farm.get_mut(pos) = Some(obj);
+        if obj == Object::UFO {
+            *farm.current_ufo_pos_mut() = pos;
+        }
+        if obj.is_cow() {
+            *farm.get_cow_count_mut() += 1;
+        }
+    }
+
+    let mut free_cells: Vec<Pos> = Pos::values(width, height)
+        .filter(|pos| {
+            let (x, y) = pos.xy();
+            x != 0 && x != height - 1 && y != 0 && y != width - 1 && x % 2 == 0 && y % 2 == 1
+        })
+        .collect();
+    free_cells.shuffle(rng);
+
+    let (silo_pos, obstacle_cells) = free_cells.split_first().expect("free_cells is non-empty");
+    *farm.get_mut(*silo_pos) = Some(Object::Silo);
+
+    let obstacles = [Object::Hay, Object::Crop, Object::Fence, Object::Barn];
+    for &pos in obstacle_cells {
+        if rng.gen_bool(0.3) {
+            let obj = *obstacles.choose(rng).expect("obstacles is non-empty");
+            *farm.get_mut(pos) = Some(obj);
+        }
+    }
+
+    farm
+}
@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -84,20 +85,32 @@ mod farm {
 
     #[derive(Debug, PartialEq, Eq, Hash)]
     pub struct Farm {
-        layout: [[Option<Object>; 7]; 7],
+        layout: Vec<Vec<Option<Object>>>,
+        width: usize,
+        height: usize,
         ufo_pos: Pos,
         cow_count: i32,
     }
 
     impl Farm {
-        pub(super) fn new() -> Self {
+        pub(super) fn new(width: usize, height: usize) -> Self {
             Farm {
-                layout: [[None; 7]; 7],
-                ufo_pos: Pos::new(0, 0),
+                layout: vec![vec![None; width]; height],
+                width,
+                height,
+                ufo_pos: Pos::new(0, 0, width, height),
                 cow_count: 0,
             }
         }
 
+        pub(super) fn width(&self) -> usize {
+            self.width
+        }
+
+        pub(super) fn height(&self) -> usize {
+            self.height
+        }
+
         /// Returns a reference to the gameboard at position `pos`.
         pub(super) fn get(&self, pos: Pos) -> Option<Object> {
             let (x, y) = pos.xy();
@@ -130,12 +143,12 @@ pub use self::farm::Farm;
 
 impl Display for Farm {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        for pos in Pos::values() {
+        for pos in Pos::values(self.width(), self.height()) {
             match self.get(pos) {
                 None => f.write_str(" ")?,
                 Some(obj) => obj.fmt(f)?,
             }
-            if pos.xy().0 == 6 {
+            if pos.xy().1 == self.width() - 1 {
                 f.write_str("\n")?
             }
         }
@@ -143,11 +156,105 @@ impl Display for Farm {
     }
 }
 
-pub struct FarmParseError;
+/// An owned, serializable copy of a `Farm`'s board. `Farm` itself does not
+/// derive `Serialize`/`Deserialize`, so a `FarmSnapshot` is what crosses a
+/// process or language boundary (e.g. to a web front-end).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FarmSnapshot {
+    pub layout: Vec<Vec<Option<Object>>>,
+    pub width: usize,
+    pub height: usize,
+    pub ufo_pos: Pos,
+    pub cow_count: i32,
+}
+
+impl Farm {
+    /// Captures the current board as an owned, serializable `FarmSnapshot`.
+    pub fn to_snapshot(&self) -> FarmSnapshot {
+        let mut layout = vec![vec![None; self.width()]; self.height()];
+        for pos in Pos::values(self.width(), self.height()) {
+            let (x, y) = pos.xy();
+            layout[x][y] = self.get(pos);
+        }
+        FarmSnapshot {
+            layout,
+            width: self.width(),
+            height: self.height(),
+            ufo_pos: self.current_ufo_pos(),
+            cow_count: self.get_cow_count(),
+        }
+    }
+
+    /// Reconstructs an owned `Farm` from a `FarmSnapshot`.
+    pub fn from_snapshot(snapshot: &FarmSnapshot) -> Self {
+        let mut farm = Farm::new(snapshot.width, snapshot.height);
+        for pos in Pos::values(snapshot.width, snapshot.height) {
+            let (x, y) = pos.xy();
+            *farm.get_mut(pos) = snapshot.layout[x][y];
+        }
+        *farm.current_ufo_pos_mut() = snapshot.ufo_pos;
+        *farm.get_cow_count_mut() = snapshot.cow_count;
+        farm
+    }
+}
+
+/// The ways `Farm::from_str` can reject a board, each carrying enough detail
+/// (the offending cell, the character found, counts) to point a caller at
+/// the exact problem.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FarmParseError {
+    UnrecognizedChar(char, Pos),
+    TooFewCells,
+    TooManyCells,
+    MissingRowTerminator(usize),
+    DuplicateUfo(Pos),
+    MissingUfo,
+    MissingRedBull,
+    DuplicateCattle(Object, Pos),
+    DuplicateSilo(Pos),
+    WrongWallCount { found: i32, expected: i32 },
+}
+impl Display for FarmParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FarmParseError::UnrecognizedChar(c, pos) => {
+                write!(f, "unrecognized character '{}' at {}", c, pos)
+            }
+            FarmParseError::TooFewCells => {
+                write!(f, "too few cells: input ended before the board was fully read")
+            }
+            FarmParseError::TooManyCells => {
+                write!(f, "too many cells: input continued after the board was fully read")
+            }
+            FarmParseError::MissingRowTerminator(row) => {
+                write!(f, "row {} is missing its newline terminator", row)
+            }
+            FarmParseError::DuplicateUfo(pos) => write!(f, "duplicate UFO at {}", pos),
+            FarmParseError::MissingUfo => write!(f, "missing UFO"),
+            FarmParseError::MissingRedBull => write!(f, "missing red bull"),
+            FarmParseError::DuplicateCattle(obj, pos) => {
+                write!(f, "duplicate {:?} at {}", obj, pos)
+            }
+            FarmParseError::DuplicateSilo(pos) => write!(f, "duplicate silo at {}", pos),
+            FarmParseError::WrongWallCount { found, expected } => write!(
+                f,
+                "wrong wall count: found {} wall cells, expected {}",
+                found, expected
+            ),
+        }
+    }
+}
+impl std::error::Error for FarmParseError {}
+
 impl FromStr for Farm {
     type Err = FarmParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut farm = Farm::new();
+        let height = s.lines().count();
+        let width = s.lines().next().map(|line| line.chars().count()).unwrap_or(0);
+        if width == 0 || height == 0 {
+            return Err(FarmParseError::TooFewCells);
+        }
+        let mut farm = Farm::new(width, height);
         let mut c = s.chars();
         let mut ufo = false;
         let mut azure_cow = false;
@@ -158,20 +265,19 @@ impl FromStr for Farm {
         let mut silo = false;
         let mut wall_count: i32 = 0;
 
-        // TODO ERROR CHECKING ---------------------------------------//////////////
-        // e.g., too few characters, too many characters, unrecognized character,
-        // unexpected template or object character,
-        //missing red bull, duplicate cattle, missing or duplicate ufo, duplicate silo
-        for x in 0..7 {
-            for y in 0..7 {
-                let pos = Pos::new(x, y);
+        for row in 0..height {
+            for col in 0..width {
+                let pos = Pos::new(row, col, width, height);
                 let put_object_with_flag = |farm: &mut Farm, flag: &mut bool, obj: Object| {
-                    *farm.get_mut(pos) = Some(obj);
                     if *flag {
-                        return Err(FarmParseError);
-                    } else {
-                        *flag = true;
+                        return Err(match obj {
+                            Object::UFO => FarmParseError::DuplicateUfo(pos),
+                            Object::Silo => FarmParseError::DuplicateSilo(pos),
+                            _ => FarmParseError::DuplicateCattle(obj, pos),
+                        });
                     }
+                    *flag = true;
+                    *farm.get_mut(pos) = Some(obj);
                     if obj == Object::UFO {
                         *farm.current_ufo_pos_mut() = pos;
                     }
@@ -191,8 +297,8 @@ impl FromStr for Farm {
                     Ok(())
                 };
                 match c.next() {
-                    None => return Err(FarmParseError),
-                    Some(c) => match c {
+                    None => return Err(FarmParseError::TooFewCells),
+                    Some(ch) => match ch {
                         'U' => put_object_with_flag(&mut farm, &mut ufo, Object::UFO)?,
                         'A' => put_object_with_flag(&mut farm, &mut azure_cow, Object::AzureCow)?,
                         'Y' => put_object_with_flag(&mut farm, &mut yellow_cow, Object::YellowCow)?,
@@ -208,23 +314,30 @@ impl FromStr for Farm {
                         '-' => put_object(&mut farm, Object::Wall2, &mut wall_count)?,
                         '+' => put_object(&mut farm, Object::Corner, &mut wall_count)?,
                         ' ' => put_object(&mut farm, Object::Empty, &mut wall_count)?,
-                        _ => return Err(FarmParseError),
+                        _ => return Err(FarmParseError::UnrecognizedChar(ch, pos)),
                     },
                 }
             }
             match c.next() {
                 Some('\n') => (),
-                _ => return Err(FarmParseError),
+                _ => return Err(FarmParseError::MissingRowTerminator(row)),
             }
         }
         if c.next().is_some() {
-            return Err(FarmParseError);
+            return Err(FarmParseError::TooManyCells);
         }
-        if red_bull == false || ufo == false {
-            return Err(FarmParseError);
+        if !ufo {
+            return Err(FarmParseError::MissingUfo);
         }
-        if wall_count != 24 {
-            return Err(FarmParseError);
+        if !red_bull {
+            return Err(FarmParseError::MissingRedBull);
+        }
+        let expected_wall_count = 2 * (width + height) as i32 - 4;
+        if wall_count != expected_wall_count {
+            return Err(FarmParseError::WrongWallCount {
+                found: wall_count,
+                expected: expected_wall_count,
+            });
         }
 
         Ok(farm)
@@ -265,27 +378,45 @@ impl Display for Direction {
 
 /// The (private) `pos` module ensures that the `Pos` type can only be created
 /// via the `new` associated function and accessed via the `x`, `y`, and `xy`
-/// methods.
+/// methods. Each `Pos` carries the `width`/`height` of the gameboard it was
+/// created for, so bounds-checking and stepping no longer assume a fixed 7x7
+/// board and work for any (possibly rectangular) board size.
 mod pos {
     use serde::{Deserialize, Serialize};
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
     pub struct Pos {
         x: usize,
         y: usize,
+        width: usize,
+        height: usize,
     }
     impl Pos {
-        pub fn new(x: usize, y: usize) -> Self {
-            if x > 6 {
-                panic!("Pos::new x (is {}) should be less than 7", x)
+        /// Creates a position `(x, y)` on a `width`x`height` gameboard, where
+        /// `x` is the row (bounded by `height`) and `y` is the column
+        /// (bounded by `width`).
+        pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+            if x >= height {
+                panic!("Pos::new x (is {}) should be less than height ({})", x, height)
+            }
+            if y >= width {
+                panic!("Pos::new y (is {}) should be less than width ({})", y, width)
             }
-            if y > 6 {
-                panic!("Pos::new y (is {}) should be less than 7", y)
+            Pos {
+                x,
+                y,
+                width,
+                height,
             }
-            Pos { x, y }
         }
         pub fn xy(&self) -> (usize, usize) {
             (self.x, self.y)
         }
+        pub fn width(&self) -> usize {
+            self.width
+        }
+        pub fn height(&self) -> usize {
+            self.height
+        }
     }
 }
 use self::pos::Pos;
@@ -296,6 +427,7 @@ impl Pos {
     /// position on the gameboard that is one step from `self` in the direction
     /// `dir` (i.e., would move off the edge of the gameboard).
     pub fn step(&self, dir: Direction) -> Option<Self> {
+        let (width, height) = (self.width(), self.height());
         let (x, y) = self.xy();
         let (x, y) = match dir {
             Direction::North => {
@@ -305,7 +437,7 @@ impl Pos {
                 (x - 1, y)
             }
             Direction::South => {
-                if x == 6 {
+                if x == height - 1 {
                     return None;
                 }
                 (x + 1, y)
@@ -317,17 +449,17 @@ impl Pos {
                 (x, y - 1)
             }
             Direction::East => {
-                if y == 6 {
+                if y == width - 1 {
                     return None;
                 }
                 (x, y + 1)
             }
         };
-        Some(Pos::new(x, y))
+        Some(Pos::new(x, y, width, height))
     }
-    /// An iterator over all positions of the gameboard.
-    pub fn values() -> impl Iterator<Item = Self> {
-        (0..7).flat_map(|y| (0..7).map(move |x| Pos::new(x, y)))
+    /// An iterator over all positions of a `width`x`height` gameboard.
+    pub fn values(width: usize, height: usize) -> impl Iterator<Item = Self> {
+        (0..height).flat_map(move |x| (0..width).map(move |y| Pos::new(x, y, width, height)))
     }
 }
 impl Display for Pos {
@@ -448,6 +580,201 @@ impl<'a> IotCS<'a> {
     }
 }
 
+/// The part of an `IotCS` state that actually varies during search: the
+/// board (`farm`) is shared and invariant across every state `solve_astar`
+/// visits, so keying `g_score`/`came_from` on the full `IotCS` (whose
+/// derived `Hash`/`Eq` walk the whole board through `farm`) would make every
+/// frontier lookup pay for rehashing the board on each probe. `StateKey`
+/// carries only the fields that distinguish one state from another.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct StateKey {
+    ufo_pos: Pos,
+    cow_collection: VecDeque<Object>,
+    red_bull_picked: bool,
+}
+impl<'a> IotCS<'a> {
+    fn key(&self) -> StateKey {
+        StateKey {
+            ufo_pos: self.ufo_pos,
+            cow_collection: self.cow_collection.clone(),
+            red_bull_picked: self.red_bull_picked,
+        }
+    }
+}
+
+/// An entry in the `solve_astar` frontier, ordered by ascending `g + h` so
+/// that `BinaryHeap` (a max-heap) pops the most promising state first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AStarEntry<'a> {
+    priority: u32,
+    state: IotCS<'a>,
+}
+impl<'a> Ord for AStarEntry<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+impl<'a> PartialOrd for AStarEntry<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> IotCS<'a> {
+    /// An admissible lower bound on the number of moves remaining to reach a
+    /// goal state. Each term counts a distinct move that cannot be avoided:
+    ///
+    /// - one move per farm cow not yet in `cow_collection`;
+    /// - one move to pick up the red bull, if it hasn't been picked up yet
+    ///   (it can only be taken once every cow is aboard, so it always costs
+    ///   at least one more move after the last cow);
+    /// - once everything is collected, `ceil(min_distance_to_any_border_cell
+    ///   / 2)` moves to reach an edge and exit, since the UFO advances two
+    ///   cells per move.
+    ///
+    /// These terms never double-count the same move, so their sum never
+    /// overestimates the true remaining distance.
+    pub fn heuristic(&self) -> u32 {
+        let collected_cows = self.cow_collection.iter().filter(|obj| obj.is_cow()).count();
+        let remaining_uncollected_cows =
+            (self.farm.get_cow_count() as usize).saturating_sub(collected_cows);
+        let red_bull_penalty = if self.red_bull_picked { 0 } else { 1 };
+        let exit_distance = if remaining_uncollected_cows == 0 && self.red_bull_picked {
+            let (x, y) = self.ufo_pos.xy();
+            let (width, height) = (self.farm.width(), self.farm.height());
+            let border_distance = x.min(height - 1 - x).min(y).min(width - 1 - y);
+            border_distance.div_ceil(2) as u32
+        } else {
+            0
+        };
+        remaining_uncollected_cows as u32 + red_bull_penalty + exit_distance
+    }
+
+    /// Runs A* search over the puzzle's state space, using `heuristic` to
+    /// guide a binary-heap frontier keyed on `g + h`. Returns the sequence of
+    /// moves from `self` to a goal state, or `None` if no such sequence
+    /// exists.
+    pub fn solve_astar(&self) -> Option<Vec<Direction>> {
+        self.solve_astar_with_stats().map(|(moves, _expanded)| moves)
+    }
+
+    /// As `solve_astar`, but also returns the number of states popped off the
+    /// frontier and expanded. The puzzle generator uses this expanded-node
+    /// count, together with the move count, to gauge how hard a candidate
+    /// board is to solve.
+    pub(crate) fn solve_astar_with_stats(&self) -> Option<(Vec<Direction>, usize)> {
+        let mut g_score: HashMap<StateKey, u32> = HashMap::new();
+        let mut came_from: HashMap<StateKey, (StateKey, Direction)> = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        let mut expanded = 0;
+
+        g_score.insert(self.key(), 0);
+        frontier.push(AStarEntry {
+            priority: self.heuristic(),
+            state: self.clone(),
+        });
+
+        while let Some(AStarEntry { state, .. }) = frontier.pop() {
+            expanded += 1;
+            if state.is_goal() {
+                let mut moves = Vec::new();
+                let mut current = state.key();
+                while let Some((prev, dir)) = came_from.remove(&current) {
+                    moves.push(dir);
+                    current = prev;
+                }
+                moves.reverse();
+                return Some((moves, expanded));
+            }
+
+            let g = g_score[&state.key()];
+            for (dir, next) in state.next() {
+                let tentative_g = g + 1;
+                let next_key = next.key();
+                if tentative_g < *g_score.get(&next_key).unwrap_or(&u32::MAX) {
+                    came_from.insert(next_key.clone(), (state.key(), dir));
+                    g_score.insert(next_key, tentative_g);
+                    frontier.push(AStarEntry {
+                        priority: tentative_g + next.heuristic(),
+                        state: next,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// An owned, serializable snapshot of a single `IotCS` state: the board
+/// layout (duplicated from the underlying `Farm` so each frame is
+/// self-contained), the UFO's position, the cattle collected so far, and
+/// whether the red bull has been picked up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IotCSSnapshot {
+    pub layout: Vec<Vec<Option<Object>>>,
+    pub ufo_pos: Pos,
+    pub cow_collection: VecDeque<Object>,
+    pub red_bull_picked: bool,
+}
+
+/// A solved puzzle, ready to be serialized to JSON and animated step by step
+/// by a front-end: the initial board, the moves that solve it, and a
+/// snapshot of the starting state followed by one after each move.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Solution {
+    pub initial: FarmSnapshot,
+    pub moves: Vec<Direction>,
+    pub frames: Vec<IotCSSnapshot>,
+}
+
+impl<'a> IotCS<'a> {
+    /// Captures the current state as an owned, serializable `IotCSSnapshot`.
+    pub fn to_snapshot(&self) -> IotCSSnapshot {
+        IotCSSnapshot {
+            layout: self.farm.to_snapshot().layout,
+            ufo_pos: self.ufo_pos,
+            cow_collection: self.cow_collection.clone(),
+            red_bull_picked: self.red_bull_picked,
+        }
+    }
+
+    /// Reconstructs an `IotCS` state from an `IotCSSnapshot`, borrowing
+    /// `farm` for the lifetime of the returned value.
+    pub fn from_snapshot(snapshot: &IotCSSnapshot, farm: &'a Farm) -> Self {
+        IotCS {
+            farm,
+            ufo_pos: snapshot.ufo_pos,
+            cow_collection: snapshot.cow_collection.clone(),
+            red_bull_picked: snapshot.red_bull_picked,
+        }
+    }
+
+    /// Solves the puzzle with `solve_astar` and replays the resulting moves
+    /// to capture a full `Solution`, suitable for animating the UFO step by
+    /// step in a front-end.
+    pub fn solve(&self) -> Option<Solution> {
+        let moves = self.solve_astar()?;
+        let mut frames = Vec::with_capacity(moves.len() + 1);
+        let mut current = self.clone();
+        frames.push(current.to_snapshot());
+        for &dir in &moves {
+            current = current
+                .next()
+                .into_iter()
+                .find(|(move_dir, _)| *move_dir == dir)
+                .map(|(_, state)| state)
+                .expect("solve_astar returned a move with no matching successor state");
+            frames.push(current.to_snapshot());
+        }
+        Some(Solution {
+            initial: self.farm.to_snapshot(),
+            moves,
+            frames,
+        })
+    }
+}
+
 impl<'a> Puzzle for IotCS<'a> {
     type Move = Direction;
 
@@ -456,7 +783,7 @@ impl<'a> Puzzle for IotCS<'a> {
             return false;
         }
         let (x, y) = self.ufo_pos.xy();
-        if x == 6 as usize || x == 0 as usize || y == 0 as usize || y == 6 as usize {
+        if x == 0 || x == self.farm.height() - 1 || y == 0 || y == self.farm.width() - 1 {
             return true;
         } else {
             return false;
@@ -479,5 +806,8 @@ impl<'a> Puzzle for IotCS<'a> {
     }
 }
 
+mod generate;
+pub use self::generate::{generate, generate_with_difficulty, Difficulty};
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,249 @@
+use super::*;
+
+const SEED: u64 = 42;
+const SEEDS: std::ops::Range<u64> = 0..20;
+
+fn bordered_farm(width: usize, height: usize) -> Farm {
+    let mut farm = Farm::new(width, height);
+    for pos in Pos::values(width, height) {
+        let (x, y) = pos.xy();
+        let on_top_or_bottom = x == 0 || x == height - 1;
+        let on_left_or_right = y == 0 || y == width - 1;
+        let obj = if on_top_or_bottom && on_left_or_right {
+            Object::Corner
+        } else if on_top_or_bottom {
+            Object::Wall2
+        } else if on_left_or_right {
+            Object::Wall1
+        } else {
+            Object::Empty
+        };
+        *farm.get_mut(pos) = Some(obj);
+    }
+    farm
+}
+
+fn place(farm: &mut Farm, x: usize, y: usize, width: usize, height: usize, obj: Object) {
+    let pos = Pos::new(x, y, width, height);
+    *farm.get_mut(pos) = Some(obj);
+    if obj == Object::UFO {
+        *farm.current_ufo_pos_mut() = pos;
+    }
+    if obj.is_cow() {
+        *farm.get_cow_count_mut() += 1;
+    }
+}
+
+/// A small 7x7 farm with no obstacles between the UFO, the cattle and the
+/// border, so its optimal solution is easy to reason about by hand.
+fn sample_farm() -> Farm {
+    let mut farm = bordered_farm(7, 7);
+    place(&mut farm, 1, 2, 7, 7, Object::UFO);
+    place(&mut farm, 1, 4, 7, 7, Object::AzureCow);
+    place(&mut farm, 3, 2, 7, 7, Object::YellowCow);
+    place(&mut farm, 3, 4, 7, 7, Object::PurpleCow);
+    place(&mut farm, 5, 2, 7, 7, Object::OrangeCow);
+    place(&mut farm, 5, 4, 7, 7, Object::RedBull);
+    farm
+}
+
+#[test]
+fn heuristic_never_overestimates_the_optimal_solve() {
+    let farm = sample_farm();
+    let state = IotCS::new(&farm);
+    let moves = state.solve_astar().expect("sample farm should be solvable");
+    assert!(state.heuristic() as usize <= moves.len());
+}
+
+#[test]
+fn heuristic_is_zero_at_a_goal_state() {
+    let farm = sample_farm();
+    let mut state = IotCS::new(&farm);
+    for dir in state.solve_astar().expect("sample farm should be solvable") {
+        state = state
+            .next()
+            .into_iter()
+            .find(|(move_dir, _)| *move_dir == dir)
+            .map(|(_, next)| next)
+            .expect("solve_astar returned a move with no matching successor state");
+    }
+    assert!(state.is_goal());
+    assert_eq!(state.heuristic(), 0);
+}
+
+const RECTANGULAR_BOARD: &str = "+-------+\n|UAYPORS|\n|BCHF   |\n|       |\n+-------+\n";
+
+#[test]
+fn parametric_board_round_trips_through_display_and_from_str() {
+    let farm = Farm::from_str(RECTANGULAR_BOARD).expect("rectangular board should parse");
+    assert_eq!(farm.to_string(), RECTANGULAR_BOARD);
+    assert_eq!(Farm::from_str(&farm.to_string()).unwrap(), farm);
+}
+
+/// Overwrites the character at `(row, col)` in `board`, where both are cell
+/// coordinates (not byte offsets), so callers can target a specific cell
+/// without hand-counting through the newlines.
+fn replace_char(board: &str, row: usize, col: usize, ch: char) -> String {
+    let stride = board.lines().next().unwrap().chars().count() + 1;
+    let idx = row * stride + col;
+    let mut chars: Vec<char> = board.chars().collect();
+    chars[idx] = ch;
+    chars.into_iter().collect()
+}
+
+#[test]
+fn unrecognized_char_reports_the_offending_cell() {
+    let board = replace_char(RECTANGULAR_BOARD, 3, 3, 'Z');
+    assert_eq!(
+        Farm::from_str(&board),
+        Err(FarmParseError::UnrecognizedChar('Z', Pos::new(3, 3, 9, 5)))
+    );
+}
+
+#[test]
+fn too_few_cells_reports_a_truncated_board() {
+    let board = &RECTANGULAR_BOARD[..RECTANGULAR_BOARD.len() - 5];
+    assert_eq!(Farm::from_str(board), Err(FarmParseError::TooFewCells));
+}
+
+#[test]
+fn missing_row_terminator_reports_the_unterminated_row() {
+    let board = replace_char(RECTANGULAR_BOARD, 1, 9, 'X');
+    assert_eq!(
+        Farm::from_str(&board),
+        Err(FarmParseError::MissingRowTerminator(1))
+    );
+}
+
+#[test]
+fn duplicate_ufo_reports_the_second_cell() {
+    let board = replace_char(RECTANGULAR_BOARD, 3, 3, 'U');
+    assert_eq!(
+        Farm::from_str(&board),
+        Err(FarmParseError::DuplicateUfo(Pos::new(3, 3, 9, 5)))
+    );
+}
+
+#[test]
+fn missing_ufo_is_reported() {
+    let board = replace_char(RECTANGULAR_BOARD, 1, 1, ' ');
+    assert_eq!(Farm::from_str(&board), Err(FarmParseError::MissingUfo));
+}
+
+#[test]
+fn missing_red_bull_is_reported() {
+    let board = replace_char(RECTANGULAR_BOARD, 1, 6, ' ');
+    assert_eq!(Farm::from_str(&board), Err(FarmParseError::MissingRedBull));
+}
+
+#[test]
+fn duplicate_cattle_reports_the_object_and_cell() {
+    let board = replace_char(RECTANGULAR_BOARD, 3, 3, 'A');
+    assert_eq!(
+        Farm::from_str(&board),
+        Err(FarmParseError::DuplicateCattle(
+            Object::AzureCow,
+            Pos::new(3, 3, 9, 5)
+        ))
+    );
+}
+
+#[test]
+fn duplicate_silo_reports_the_second_cell() {
+    let board = replace_char(RECTANGULAR_BOARD, 3, 3, 'S');
+    assert_eq!(
+        Farm::from_str(&board),
+        Err(FarmParseError::DuplicateSilo(Pos::new(3, 3, 9, 5)))
+    );
+}
+
+#[test]
+fn wrong_wall_count_reports_found_and_expected() {
+    let board = replace_char(RECTANGULAR_BOARD, 2, 1, ' ');
+    assert_eq!(
+        Farm::from_str(&board),
+        Err(FarmParseError::WrongWallCount {
+            found: 23,
+            expected: 24
+        })
+    );
+}
+
+#[test]
+fn farm_snapshot_json_round_trips() {
+    let farm = Farm::from_str(RECTANGULAR_BOARD).unwrap();
+    let snapshot = farm.to_snapshot();
+    let json = serde_json::to_string(&snapshot).expect("snapshot should serialize");
+    let restored: FarmSnapshot = serde_json::from_str(&json).expect("snapshot should deserialize");
+    assert_eq!(restored, snapshot);
+}
+
+#[test]
+fn iotcs_snapshot_json_round_trips() {
+    let farm = Farm::from_str(RECTANGULAR_BOARD).unwrap();
+    let snapshot = IotCS::new(&farm).to_snapshot();
+    let json = serde_json::to_string(&snapshot).expect("snapshot should serialize");
+    let restored: IotCSSnapshot =
+        serde_json::from_str(&json).expect("snapshot should deserialize");
+    assert_eq!(restored, snapshot);
+}
+
+#[test]
+fn solution_json_round_trips_and_replays_to_a_goal() {
+    let farm = sample_farm();
+    let solution = IotCS::new(&farm)
+        .solve()
+        .expect("sample farm should be solvable");
+    assert_eq!(solution.frames.len(), solution.moves.len() + 1);
+    let last = solution.frames.last().expect("solution has at least one frame");
+    assert!(IotCS::from_snapshot(last, &farm).is_goal());
+
+    let json = serde_json::to_string(&solution).expect("solution should serialize");
+    let restored: Solution = serde_json::from_str(&json).expect("solution should deserialize");
+    assert_eq!(restored, solution);
+}
+
+#[test]
+fn generate_is_reproducible_for_a_fixed_seed() {
+    let first = generate(SEED).expect("generate should find a solvable board");
+    let second = generate(SEED).expect("generate should find a solvable board");
+    assert_eq!(first, second);
+}
+
+#[test]
+fn every_difficulty_is_reachable_across_seeds() {
+    for seed in SEEDS {
+        assert!(generate(seed).is_some(), "generate({}) found no board", seed);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Expert] {
+            assert!(
+                generate_with_difficulty(seed, difficulty).is_some(),
+                "generate_with_difficulty({}, {:?}) found no board",
+                seed,
+                difficulty
+            );
+        }
+    }
+}
+
+#[test]
+fn generated_board_is_solvable() {
+    let farm = generate(SEED).expect("generate should find a solvable board");
+    assert!(IotCS::new(&farm).solve_astar().is_some());
+}
+
+#[test]
+fn farm_snapshot_round_trips() {
+    let farm = generate(SEED).expect("generate should find a solvable board");
+    let snapshot = farm.to_snapshot();
+    let restored = Farm::from_snapshot(&snapshot);
+    assert_eq!(restored.to_snapshot(), snapshot);
+}
+
+#[test]
+fn iotcs_snapshot_round_trips() {
+    let farm = generate(SEED).expect("generate should find a solvable board");
+    let state = IotCS::new(&farm);
+    let snapshot = state.to_snapshot();
+    let restored = IotCS::from_snapshot(&snapshot, &farm);
+    assert_eq!(restored.to_snapshot(), snapshot);
+}